@@ -1,4 +1,4 @@
-use cidr::ipv4::{Ipv4Cidr, Ipv4CidrList};
+use cidr::ipv4::{Ipv4Cidr, Ipv4CidrList, Ipv4Range};
 use salak::*;
 use std::io::{self, BufRead};
 use std::net::Ipv4Addr;
@@ -27,32 +27,32 @@ fn main() {
     let option = env.get::<Options>().unwrap();
 
     let mode = option.mode == "range";
-    for line in stdin.lock().lines() {
-        if let Ok(l) = line {
-            if mode {
-                let v: Vec<&str> = l.split(&option.sep).collect();
-                if v.len() >= 2 {
-                    fn parse_block(f: &str, t: &str) -> Ipv4CidrList {
-                        if let (Ok(f), Ok(t)) = (Ipv4Addr::from_str(f), Ipv4Addr::from_str(t)) {
-                            return Ipv4CidrList::from_ip_range(f, t);
+    for l in stdin.lock().lines().map_while(Result::ok) {
+        if mode {
+            let v: Vec<&str> = l.split(&option.sep).collect();
+            if v.len() >= 2 {
+                fn parse_block(f: &str, t: &str) -> Ipv4CidrList {
+                    if let (Ok(f), Ok(t)) = (Ipv4Addr::from_str(f), Ipv4Addr::from_str(t)) {
+                        if let Ok(range) = Ipv4Range::new(f, t) {
+                            return range.to_cidr_list();
                         }
-                        Ipv4CidrList::new()
-                    }
-                    for (_, block) in parse_block(v[0], v[1]).into_iter() {
-                        list.insert(block);
                     }
+                    Ipv4CidrList::new()
                 }
-            } else {
-                fn add(list: &mut Ipv4CidrList, ip: &str) {
-                    if let Ok(ip) = Ipv4Cidr::from_str(ip.trim()) {
-                        list.insert(ip);
-                    }
+                for (_, block) in parse_block(v[0], v[1]).into_iter() {
+                    list.insert(block);
                 }
-                match &l.strip_prefix("-") {
-                    Some(ip) => add(&mut rem, ip),
-                    _ => add(&mut list, &l),
+            }
+        } else {
+            fn add(list: &mut Ipv4CidrList, ip: &str) {
+                if let Ok(ip) = Ipv4Cidr::from_str(ip.trim()) {
+                    list.insert(ip);
                 }
             }
+            match &l.strip_prefix("-") {
+                Some(ip) => add(&mut rem, ip),
+                _ => add(&mut list, &l),
+            }
         }
     }
     for (_, cidr) in rem {
@@ -60,7 +60,7 @@ fn main() {
     }
 
     if option.count {
-        print!("{}", list.count());
+        print!("{}", list.addresses().count());
         return;
     }
 
@@ -1,7 +1,8 @@
 use actix_web::{get, web, App, HttpServer, Responder};
 use cidr::ipv4::Ipv4Cidr;
 use cidr::ipv4::Ipv4CidrList;
-use std::collections::BTreeMap;
+use cidr::ipv4::Ipv4Range;
+use cidr::trie::Trie;
 use std::collections::HashMap;
 use std::fs::File;
 use std::io::BufRead;
@@ -24,33 +25,39 @@ async fn main() -> std::io::Result<()> {
 
 pub struct Ipv4CidrRange {
     loc_map: HashMap<u128, String>,
-    ip_map: BTreeMap<u32, (Ipv4Cidr, u128)>,
+    ip_trie: Trie<(Ipv4Cidr, u128)>,
+}
+
+impl Default for Ipv4CidrRange {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Ipv4CidrRange {
     pub fn new() -> Self {
         Ipv4CidrRange {
             loc_map: HashMap::new(),
-            ip_map: BTreeMap::new(),
+            ip_trie: Trie::new(32),
         }
     }
 
     pub fn from_file(file: &str, sep: &str) -> Self {
         let mut r = Ipv4CidrRange::new();
         if let Ok(f) = File::open(file) {
-            for line in BufReader::new(f).lines() {
-                if let Ok(l) = line {
-                    let v: Vec<&str> = l.split(sep).collect();
-                    if v.len() > 2 {
-                        fn parse_block(f: &str, t: &str) -> Ipv4CidrList {
-                            if let (Ok(f), Ok(t)) = (Ipv4Addr::from_str(f), Ipv4Addr::from_str(t)) {
-                                return Ipv4CidrList::from_ip_range(f, t);
+            for l in BufReader::new(f).lines().map_while(Result::ok) {
+                let v: Vec<&str> = l.split(sep).collect();
+                if v.len() > 2 {
+                    fn parse_block(f: &str, t: &str) -> Ipv4CidrList {
+                        if let (Ok(f), Ok(t)) = (Ipv4Addr::from_str(f), Ipv4Addr::from_str(t)) {
+                            if let Ok(range) = Ipv4Range::new(f, t) {
+                                return range.to_cidr_list();
                             }
-                            Ipv4CidrList::new()
-                        }
-                        for (_, block) in parse_block(v[0], v[1]).into_iter() {
-                            r.insert(block, v[2].to_owned());
                         }
+                        Ipv4CidrList::new()
+                    }
+                    for (_, block) in parse_block(v[0], v[1]).into_iter() {
+                        r.insert(block, v[2].to_owned());
                     }
                 }
             }
@@ -61,9 +68,9 @@ impl Ipv4CidrRange {
     fn hash(loc: &str) -> u128 {
         let vs = md5::compute(loc).0;
         let mut x = vs[0] as u128;
-        for i in 1..16 {
+        for v in vs.iter().skip(1) {
             x <<= 4;
-            x += vs[i] as u128;
+            x += *v as u128;
         }
         x
     }
@@ -71,33 +78,17 @@ impl Ipv4CidrRange {
     pub fn insert(&mut self, cidr: Ipv4Cidr, loc: String) {
         let h = Self::hash(&loc);
         self.loc_map.entry(h).or_insert(loc);
-        self.ip_map.insert(u32::from(cidr.first_ip()), (cidr, h));
+        let net = u32::from(cidr.first_ip()) as u128;
+        let mask = cidr.mask();
+        self.ip_trie.insert(net, mask, (cidr, h));
     }
 
     pub fn get(&self, ip: &Ipv4Addr) -> Option<String> {
-        let mut net = u32::from(ip.clone());
-        let mut size = 0;
-        loop {
-            if let Some((v, h)) = self.ip_map.get(&net) {
-                if !v.contains_ip(ip) {
-                    return None;
-                }
-                return self.loc_map.get(h).map(|r| r.to_string());
-            }
-            if size == 32 {
-                return None;
-            }
-            size += 1;
-            net >>= size;
-            while net & 1 == 0 {
-                net >>= 1;
-                size += 1;
-                if size == 32 {
-                    return None;
-                }
-            }
-            net = (net - 1) << size;
-            size += 1;
+        let net = u32::from(*ip) as u128;
+        let (v, h) = self.ip_trie.longest_match(net, 32)?;
+        if !v.contains_ip(ip) {
+            return None;
         }
+        self.loc_map.get(h).map(|r| r.to_string())
     }
 }
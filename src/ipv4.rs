@@ -20,6 +20,9 @@ use std::str::FromStr;
 
 use std::collections::BTreeMap;
 use std::collections::HashSet;
+use std::collections::VecDeque;
+
+use crate::trie::Trie;
 
 /// Ipv4 CIDR structure
 #[derive(Eq, PartialEq, Debug, Clone)]
@@ -67,12 +70,25 @@ impl Ipv4Cidr {
         32 - self.size
     }
 
+    /// Get the mask in dotted-decimal netmask form, e.g. `255.255.255.0`.
+    pub fn netmask(&self) -> Ipv4Addr {
+        let mask = self.mask();
+        let bits = if mask == 0 { 0 } else { u32::MAX << (32 - mask) };
+        Ipv4Addr::from(bits)
+    }
+
+    /// Format the CIDR using a dotted-decimal netmask suffix instead of a prefix length,
+    /// e.g. `192.168.0.0/255.255.255.0`.
+    pub fn to_string_netmask(&self) -> String {
+        format!("{}/{}", self.first_ip(), self.netmask())
+    }
+
     /// Check if the ip is in this CIDR block.
     pub fn contains_ip(&self, ip: &Ipv4Addr) -> bool {
         if self.size == 32 {
             return true;
         }
-        self.net >> self.size == u32::from(ip.clone()) >> self.size
+        self.net >> self.size == u32::from(*ip) >> self.size
     }
 
     /// Check if the current CIDR block contains other CIDR block.
@@ -98,6 +114,87 @@ impl Ipv4Cidr {
         let (f, t) = self.to_range();
         (Ipv4Addr::from(f), Ipv4Addr::from(t))
     }
+
+    /// Iterate over every address in this CIDR block.
+    pub fn addresses(&self) -> Ipv4AddrIter {
+        let (f, t) = self.to_range();
+        Ipv4AddrIter::new(f, t)
+    }
+
+    /// Iterate over every host address in this CIDR block, skipping the network and broadcast
+    /// address for masks less than 31.
+    pub fn hosts(&self) -> Ipv4AddrIter {
+        let (f, t) = self.to_range();
+        if self.mask() < 31 {
+            Ipv4AddrIter::new(f + 1, t - 1)
+        } else {
+            Ipv4AddrIter::new(f, t)
+        }
+    }
+}
+
+/// Iterator over every address in an [`Ipv4Cidr`] block, produced by [`Ipv4Cidr::addresses`] and
+/// [`Ipv4Cidr::hosts`].
+pub struct Ipv4AddrIter {
+    next: u32,
+    next_back: u32,
+    done: bool,
+}
+
+impl Ipv4AddrIter {
+    fn new(from: u32, to: u32) -> Self {
+        Ipv4AddrIter {
+            next: from,
+            next_back: to,
+            done: from > to,
+        }
+    }
+}
+
+impl Iterator for Ipv4AddrIter {
+    type Item = Ipv4Addr;
+    fn next(&mut self) -> Option<Ipv4Addr> {
+        if self.done {
+            return None;
+        }
+        let v = self.next;
+        if v == self.next_back {
+            self.done = true;
+        } else {
+            self.next = v.saturating_add(1);
+        }
+        Some(Ipv4Addr::from(v))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.len();
+        (len, Some(len))
+    }
+}
+
+impl DoubleEndedIterator for Ipv4AddrIter {
+    fn next_back(&mut self) -> Option<Ipv4Addr> {
+        if self.done {
+            return None;
+        }
+        let v = self.next_back;
+        if v == self.next {
+            self.done = true;
+        } else {
+            self.next_back = v.saturating_sub(1);
+        }
+        Some(Ipv4Addr::from(v))
+    }
+}
+
+impl ExactSizeIterator for Ipv4AddrIter {
+    fn len(&self) -> usize {
+        if self.done {
+            0
+        } else {
+            (self.next_back - self.next) as usize + 1
+        }
+    }
 }
 
 impl FromStr for Ipv4Cidr {
@@ -105,7 +202,7 @@ impl FromStr for Ipv4Cidr {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         lazy_static! {
             static ref RE: Regex = Regex::new(
-                r"^((1?[0-9]{1,2}|2[0-4][0-9]|25[0-5])(\.(1?[0-9]{1,2}|2[0-4][0-9]|25[0-5])){3})(/([0-9]|[12][0-9]|3[012]))?$"
+                r"^((1?[0-9]{1,2}|2[0-4][0-9]|25[0-5])(\.(1?[0-9]{1,2}|2[0-4][0-9]|25[0-5])){3})(/(.+))?$"
             )
             .expect(NOT_POSSIBLE);
         }
@@ -113,7 +210,7 @@ impl FromStr for Ipv4Cidr {
         match RE.captures(s) {
             Some(ref v) => {
                 let ms = match v.get(6) {
-                    Some(v) => v.as_str().parse::<u8>().expect(NOT_POSSIBLE),
+                    Some(v) => parse_mask(v.as_str())?,
                     _ => 32,
                 };
                 let ip =
@@ -125,6 +222,24 @@ impl FromStr for Ipv4Cidr {
     }
 }
 
+/// Parse a CIDR's mask suffix, accepting either a prefix length (`24`) or a
+/// dotted-decimal netmask (`255.255.255.0`).
+fn parse_mask(s: &str) -> Result<u8, String> {
+    if let Ok(prefix) = s.parse::<u8>() {
+        if prefix > 32 {
+            return Err("Mask should equal or less then 32.".to_string());
+        }
+        return Ok(prefix);
+    }
+    let mask = u32::from(Ipv4Addr::from_str(s).map_err(|_| "Invalid CIDR format.".to_owned())?);
+    let prefix = mask.leading_ones() as u8;
+    let canonical = if prefix == 0 { 0 } else { u32::MAX << (32 - prefix) };
+    if mask != canonical {
+        return Err(format!("Netmask {} is not a contiguous run of high bits.", s));
+    }
+    Ok(prefix)
+}
+
 impl From<Ipv4Addr> for Ipv4Cidr {
     fn from(addr: Ipv4Addr) -> Self {
         Ipv4Cidr::from(u32::from(addr))
@@ -144,15 +259,32 @@ impl Display for Ipv4Cidr {
 }
 
 /// Ipv4 CIDR collection structure.
-#[derive(Eq, PartialEq, Clone)]
+#[derive(Clone)]
 pub struct Ipv4CidrList {
     inner: BTreeMap<u32, Ipv4Cidr>,
+    trie: Trie<Ipv4Cidr>,
+}
+
+impl std::fmt::Debug for Ipv4CidrList {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        f.debug_struct("Ipv4CidrList")
+            .field("inner", &self.inner)
+            .finish()
+    }
+}
+
+impl PartialEq for Ipv4CidrList {
+    fn eq(&self, other: &Self) -> bool {
+        self.inner == other.inner
+    }
 }
 
+impl Eq for Ipv4CidrList {}
+
 impl Display for Ipv4CidrList {
     fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
         for (&_, v) in self.inner.iter() {
-            write!(f, "{}\n", v.to_string())?;
+            writeln!(f, "{}", v)?;
         }
         Ok(())
     }
@@ -166,11 +298,143 @@ impl IntoIterator for Ipv4CidrList {
     }
 }
 
+/// Iterator over every address across all CIDR blocks in an [`Ipv4CidrList`], produced by
+/// [`Ipv4CidrList::addresses`].
+pub struct Ipv4CidrListAddrIter {
+    iters: VecDeque<Ipv4AddrIter>,
+    len: usize,
+}
+
+impl Ipv4CidrListAddrIter {
+    fn new(ranges: Vec<(u32, u32)>) -> Self {
+        let len = ranges.iter().map(|&(f, t)| (t - f) as usize + 1).sum();
+        let iters = ranges.into_iter().map(|(f, t)| Ipv4AddrIter::new(f, t)).collect();
+        Ipv4CidrListAddrIter { iters, len }
+    }
+}
+
+impl Iterator for Ipv4CidrListAddrIter {
+    type Item = Ipv4Addr;
+    fn next(&mut self) -> Option<Ipv4Addr> {
+        while let Some(front) = self.iters.front_mut() {
+            if let Some(addr) = front.next() {
+                self.len -= 1;
+                return Some(addr);
+            }
+            self.iters.pop_front();
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl DoubleEndedIterator for Ipv4CidrListAddrIter {
+    fn next_back(&mut self) -> Option<Ipv4Addr> {
+        while let Some(back) = self.iters.back_mut() {
+            if let Some(addr) = back.next_back() {
+                self.len -= 1;
+                return Some(addr);
+            }
+            self.iters.pop_back();
+        }
+        None
+    }
+}
+
+impl ExactSizeIterator for Ipv4CidrListAddrIter {
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+/// A contiguous, inclusive range of Ipv4 addresses, e.g. `1.0.0.0-1.0.0.255`.
+#[derive(Eq, PartialEq, Debug, Clone)]
+pub struct Ipv4Range {
+    /// First IP in the range.
+    pub start: Ipv4Addr,
+    /// Last IP in the range.
+    pub end: Ipv4Addr,
+}
+
+impl Ipv4Range {
+    /// Create a new range. Returns an error if `start` is after `end`.
+    pub fn new(start: Ipv4Addr, end: Ipv4Addr) -> Result<Self, String> {
+        if u32::from(start) > u32::from(end) {
+            return Err("Range start must not be after end.".to_string());
+        }
+        Ok(Ipv4Range { start, end })
+    }
+
+    /// Check if the ip is in this range.
+    pub fn contains_ip(&self, ip: &Ipv4Addr) -> bool {
+        u32::from(self.start) <= u32::from(*ip) && u32::from(*ip) <= u32::from(self.end)
+    }
+
+    /// Number of addresses in this range.
+    pub fn len(&self) -> usize {
+        (u32::from(self.end) - u32::from(self.start)) as usize + 1
+    }
+
+    /// A range always contains at least one address.
+    pub fn is_empty(&self) -> bool {
+        false
+    }
+
+    /// Iterate over every address in this range.
+    pub fn iter(&self) -> Ipv4AddrIter {
+        Ipv4AddrIter::new(u32::from(self.start), u32::from(self.end))
+    }
+
+    /// Convert this range to a normalized collection of CIDR blocks covering it.
+    pub fn to_cidr_list(&self) -> Ipv4CidrList {
+        Ipv4CidrList::from_range(u32::from(self.start), u32::from(self.end))
+    }
+}
+
+impl FromStr for Ipv4Range {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.splitn(2, '-');
+        let from = parts.next().ok_or_else(|| "Invalid range format.".to_owned())?;
+        let to = parts
+            .next()
+            .ok_or_else(|| "Invalid range format.".to_owned())?;
+        let start =
+            Ipv4Addr::from_str(from.trim()).map_err(|_| "Invalid range format.".to_owned())?;
+        let end = Ipv4Addr::from_str(to.trim()).map_err(|_| "Invalid range format.".to_owned())?;
+        Ipv4Range::new(start, end)
+    }
+}
+
+impl Display for Ipv4Range {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        write!(f, "{}-{}", self.start, self.end)
+    }
+}
+
+impl IntoIterator for Ipv4Range {
+    type Item = Ipv4Addr;
+    type IntoIter = Ipv4AddrIter;
+    fn into_iter(self) -> Ipv4AddrIter {
+        Ipv4AddrIter::new(u32::from(self.start), u32::from(self.end))
+    }
+}
+
+impl Default for Ipv4CidrList {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Ipv4CidrList {
     /// Create empty collection.
     pub fn new() -> Self {
         Ipv4CidrList {
             inner: BTreeMap::new(),
+            trie: Trie::new(32),
         }
     }
 
@@ -195,7 +459,7 @@ impl Ipv4CidrList {
                 list.insert(block);
                 return;
             }
-            let mid = (f << 1) + 1 << m - 1;
+            let mid = ((f << 1) + 1) << (m - 1);
             build(from, mid - 1, list);
             build(mid, to, list);
         }
@@ -219,7 +483,7 @@ impl Ipv4CidrList {
     }
 
     /// Export CIDR blocks to ip ranges, normally ip ranges item size is smaller than CIDR blocks.
-    pub fn to_range(&self) -> Vec<(Ipv4Addr, Ipv4Addr)> {
+    pub fn to_ranges(&self) -> Vec<Ipv4Range> {
         let mut v = vec![];
         let mut iter = self.iter();
         let mut f;
@@ -228,21 +492,32 @@ impl Ipv4CidrList {
         } else {
             return v;
         }
-        while let Some((_, cidr)) = iter.next() {
+        for (_, cidr) in iter {
             let t = cidr.to_range();
             if t.0 == f.1 + 1 {
                 f.1 = t.1;
             } else {
-                v.push((Ipv4Addr::from(f.0), Ipv4Addr::from(f.1)));
+                v.push(Ipv4Range::new(Ipv4Addr::from(f.0), Ipv4Addr::from(f.1)).expect(NOT_POSSIBLE));
+                f = t;
             }
         }
-        v.push((Ipv4Addr::from(f.0), Ipv4Addr::from(f.1)));
+        v.push(Ipv4Range::new(Ipv4Addr::from(f.0), Ipv4Addr::from(f.1)).expect(NOT_POSSIBLE));
         v
     }
 
+    /// Iterate over every address across all CIDR blocks, in order.
+    pub fn addresses(&self) -> Ipv4CidrListAddrIter {
+        let ranges = self
+            .to_ranges()
+            .into_iter()
+            .map(|r| (u32::from(r.start), u32::from(r.end)))
+            .collect();
+        Ipv4CidrListAddrIter::new(ranges)
+    }
+
     /// Check if collection contains ip.
     pub fn contains_ip(&self, ip: &Ipv4Addr) -> bool {
-        self.contains_cidr(&Ipv4Cidr::from(ip.clone()))
+        self.contains_cidr(&Ipv4Cidr::from(*ip))
     }
 
     /// Check if collection contains CIDR block.
@@ -251,29 +526,11 @@ impl Ipv4CidrList {
     }
 
     /// Get the parent CIDR block with specified CIDR block.
+    ///
+    /// Backed by the internal radix [`Trie`], this is a deterministic O(32) lookup regardless
+    /// of how many blocks the collection holds.
     pub fn search_parent(&self, cidr: &Ipv4Cidr) -> Option<&Ipv4Cidr> {
-        let mut net = cidr.net;
-        let mut size = cidr.size;
-        loop {
-            if let Some(v) = self.inner.get(&net) {
-                if v.size >= size {
-                    return Some(v);
-                }
-            }
-            if size == 32 {
-                return None;
-            }
-            net >>= size;
-            while net & 1 == 0 {
-                net >>= 1;
-                size += 1;
-                if size == 32 {
-                    return None;
-                }
-            }
-            net = (net - 1) << size;
-            size += 1;
-        }
+        self.trie.longest_match(cidr.net as u128, cidr.mask())
     }
 
     fn delete_in_range(&mut self, cidr: &Ipv4Cidr) -> bool {
@@ -281,12 +538,13 @@ impl Ipv4CidrList {
         let mut rem = LinkedList::new();
         for (&k, v) in self.inner.range(f..=t) {
             if cidr.contains_cidr(v) {
-                rem.push_back(k);
+                rem.push_back((k, v.mask()));
             }
         }
         let changed = !rem.is_empty();
-        for k in rem {
+        for (k, mask) in rem {
             self.inner.remove(&k);
+            self.trie.remove(k as u128, mask);
         }
         changed
     }
@@ -308,12 +566,15 @@ impl Ipv4CidrList {
                 };
                 if let Some(v) = self.inner.get(&pair) {
                     if v.size == cidr.size {
+                        let mask = v.mask();
                         self.inner.remove(&pair);
+                        self.trie.remove(pair as u128, mask);
                         cidr = Ipv4Cidr::new(pair, 31 - cidr.size).expect(NOT_POSSIBLE);
                         continue;
                     }
                 }
             }
+            self.trie.insert(cidr.net as u128, cidr.mask(), cidr.clone());
             self.inner.insert(cidr.net, cidr);
             return true;
         }
@@ -331,6 +592,7 @@ impl Ipv4CidrList {
             }
             let v = v.clone();
             self.inner.remove(&v.net);
+            self.trie.remove(v.net as u128, v.mask());
             let (a2, a3) = cidr.to_range();
             let (a1, a4) = v.to_range();
             if a1 < a2 {
@@ -347,6 +609,150 @@ impl Ipv4CidrList {
         }
         true
     }
+
+    /// Return a new collection containing every CIDR block in either collection.
+    pub fn union(&self, other: &Self) -> Self {
+        let mut list = self.clone();
+        for (_, cidr) in other.iter() {
+            list.insert(cidr.clone());
+        }
+        list
+    }
+
+    /// Return a new collection covering only the addresses covered by both collections, e.g.
+    /// `10.0.0.0/8` intersected with `10.0.0.0/16` gives `10.0.0.0/16`.
+    pub fn intersection(&self, other: &Self) -> Self {
+        let a = self.to_ranges();
+        let b = other.to_ranges();
+        let mut list = Ipv4CidrList::new();
+        let (mut i, mut j) = (0, 0);
+        while i < a.len() && j < b.len() {
+            let (a0, a1) = (u32::from(a[i].start), u32::from(a[i].end));
+            let (b0, b1) = (u32::from(b[j].start), u32::from(b[j].end));
+            let start = a0.max(b0);
+            let end = a1.min(b1);
+            if start <= end {
+                for (_, cidr) in Self::from_range(start, end) {
+                    list.insert(cidr);
+                }
+            }
+            if a1 < b1 {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+        list
+    }
+
+    /// Return a new collection covering the addresses of this collection that are not covered
+    /// by `other`; blocks that `other` partly covers are split into smaller blocks.
+    pub fn difference(&self, other: &Self) -> Self {
+        let mut list = self.clone();
+        for (_, cidr) in other.iter() {
+            list.remove(cidr);
+        }
+        list
+    }
+
+    /// Return a new collection containing every block not covered by this collection, relative
+    /// to `0.0.0.0/0`.
+    pub fn complement(&self) -> Self {
+        let mut list = Ipv4CidrList::new();
+        list.insert(Ipv4Cidr::new(0, 0).expect(NOT_POSSIBLE));
+        for (_, cidr) in self.iter() {
+            list.remove(cidr);
+        }
+        list
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Ipv4Cidr {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Ipv4Cidr {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        Ipv4Cidr::from_str(&s).map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Ipv4CidrList {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        use serde::ser::SerializeSeq;
+        let mut seq = serializer.serialize_seq(Some(self.inner.len()))?;
+        for (_, cidr) in self.iter() {
+            seq.serialize_element(&cidr.to_string())?;
+        }
+        seq.end()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Ipv4CidrList {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let strs = Vec::<String>::deserialize(deserializer)?;
+        let mut list = Ipv4CidrList::new();
+        for s in strs {
+            let cidr = Ipv4Cidr::from_str(&s).map_err(serde::de::Error::custom)?;
+            list.insert(cidr);
+        }
+        Ok(list)
+    }
+}
+
+#[cfg(feature = "serde")]
+#[cfg(test)]
+mod serde_tests {
+    use super::*;
+
+    #[test]
+    fn cidr_round_trip_tests() {
+        let cidr = Ipv4Cidr::from_str("192.168.0.0/24").unwrap();
+        let json = serde_json::to_string(&cidr).unwrap();
+        assert_eq!("\"192.168.0.0/24\"", json);
+        assert_eq!(cidr, serde_json::from_str(&json).unwrap());
+    }
+
+    #[test]
+    fn cidr_list_round_trip_tests() {
+        // Deserializing overlapping/adjacent entries must re-merge through `insert`, just like
+        // constructing the list directly would.
+        let json = r#"["10.0.0.0/25", "10.0.0.128/25", "192.168.0.0/16"]"#;
+        let list: Ipv4CidrList = serde_json::from_str(json).unwrap();
+
+        let mut expected = Ipv4CidrList::new();
+        expected.insert(Ipv4Cidr::from_str("10.0.0.0/24").unwrap());
+        expected.insert(Ipv4Cidr::from_str("192.168.0.0/16").unwrap());
+        assert_eq!(expected, list);
+
+        let round_tripped: Ipv4CidrList = serde_json::from_str(&serde_json::to_string(&list).unwrap()).unwrap();
+        assert_eq!(list, round_tripped);
+    }
+
+    #[test]
+    fn cidr_deserialize_invalid_string_tests() {
+        let err = serde_json::from_str::<Ipv4Cidr>("\"not-a-cidr\"").unwrap_err();
+        assert!(err.to_string().contains("Invalid CIDR format."));
+    }
 }
 
 #[cfg(test)]
@@ -365,6 +771,21 @@ mod tests {
             Ipv4Cidr::new(127 << 24, 8).unwrap().to_string()
         );
     }
+    #[test]
+    fn netmask_parse_tests() {
+        let cidr = Ipv4Cidr::from_str("192.168.0.0/255.255.255.0").unwrap();
+        assert_eq!("192.168.0.0/24", cidr.to_string());
+        assert_eq!("255.255.255.0", cidr.netmask().to_string());
+        assert_eq!("192.168.0.0/255.255.255.0", cidr.to_string_netmask());
+
+        assert_eq!(
+            Ipv4Cidr::from_str("10.0.0.0/8").unwrap(),
+            Ipv4Cidr::from_str("10.0.0.0/255.0.0.0").unwrap()
+        );
+
+        assert!(Ipv4Cidr::from_str("192.168.0.0/255.0.255.0").is_err());
+    }
+
     #[test]
     fn block_list_tests() {
         let mut list = Ipv4CidrList::new();
@@ -415,6 +836,123 @@ mod tests {
         // println!("{}", &list)
     }
 
+    #[test]
+    fn set_algebra_tests() {
+        let mut a = Ipv4CidrList::new();
+        a.insert(Ipv4Cidr::from_str("10.0.0.0/8").unwrap());
+        let mut b = Ipv4CidrList::new();
+        b.insert(Ipv4Cidr::from_str("10.0.0.0/16").unwrap());
+        b.insert(Ipv4Cidr::from_str("192.168.0.0/16").unwrap());
+
+        let mut union = Ipv4CidrList::new();
+        union.insert(Ipv4Cidr::from_str("10.0.0.0/8").unwrap());
+        union.insert(Ipv4Cidr::from_str("192.168.0.0/16").unwrap());
+        assert_eq!(union, a.union(&b));
+
+        let mut inter = Ipv4CidrList::new();
+        inter.insert(Ipv4Cidr::from_str("10.0.0.0/16").unwrap());
+        assert_eq!(inter, a.intersection(&b));
+
+        let mut diff = Ipv4CidrList::new();
+        diff.remove(&Ipv4Cidr::new(0, 0).unwrap());
+        diff.insert(Ipv4Cidr::from_str("10.0.0.0/8").unwrap());
+        diff.remove(&Ipv4Cidr::from_str("10.0.0.0/16").unwrap());
+        assert_eq!(diff, a.difference(&b));
+
+        let mut full = Ipv4CidrList::new();
+        full.insert(Ipv4Cidr::new(0, 0).unwrap());
+        assert_eq!(full, a.union(&a.complement()));
+    }
+
+    #[test]
+    fn intersection_multi_block_tests() {
+        // Three disjoint blocks per side, each overlapping its counterpart, so a buggy
+        // `to_ranges()` that only ever compares the first run against the first run would
+        // silently drop the second and third overlaps.
+        let mut a = Ipv4CidrList::new();
+        a.insert(Ipv4Cidr::from_str("10.0.0.0/24").unwrap());
+        a.insert(Ipv4Cidr::from_str("20.0.0.0/24").unwrap());
+        a.insert(Ipv4Cidr::from_str("30.0.0.0/24").unwrap());
+
+        let mut b = Ipv4CidrList::new();
+        b.insert(Ipv4Cidr::from_str("10.0.0.0/25").unwrap());
+        b.insert(Ipv4Cidr::from_str("20.0.0.128/25").unwrap());
+        b.insert(Ipv4Cidr::from_str("30.0.0.64/26").unwrap());
+
+        let mut expected = Ipv4CidrList::new();
+        expected.insert(Ipv4Cidr::from_str("10.0.0.0/25").unwrap());
+        expected.insert(Ipv4Cidr::from_str("20.0.0.128/25").unwrap());
+        expected.insert(Ipv4Cidr::from_str("30.0.0.64/26").unwrap());
+
+        assert_eq!(expected, a.intersection(&b));
+    }
+
+    #[test]
+    fn address_iter_tests() {
+        let cidr = Ipv4Cidr::from_str("192.168.0.0/30").unwrap();
+        let addrs: Vec<Ipv4Addr> = cidr.addresses().collect();
+        assert_eq!(4, addrs.len());
+        assert_eq!(4, cidr.addresses().len());
+        assert_eq!(addrs[0], Ipv4Addr::from_str("192.168.0.0").unwrap());
+        assert_eq!(addrs[3], Ipv4Addr::from_str("192.168.0.3").unwrap());
+        assert_eq!(addrs, cidr.addresses().rev().rev().collect::<Vec<_>>());
+
+        let hosts: Vec<Ipv4Addr> = cidr.hosts().collect();
+        assert_eq!(vec![addrs[1], addrs[2]], hosts);
+
+        let p2p = Ipv4Cidr::from_str("192.168.0.0/31").unwrap();
+        assert_eq!(2, p2p.hosts().len());
+
+        let single = Ipv4Cidr::from_str("192.168.0.1/32").unwrap();
+        assert_eq!(1, single.hosts().len());
+
+        let mut list = Ipv4CidrList::new();
+        list.insert(Ipv4Cidr::from_str("10.0.0.0/31").unwrap());
+        list.insert(Ipv4Cidr::from_str("10.0.0.4/31").unwrap());
+        assert_eq!(4, list.addresses().len());
+        assert_eq!(4, list.addresses().count());
+
+        // Three disjoint blocks: a buggy `to_ranges()` that never advances past the first run
+        // would repeat the first block's addresses instead of visiting the other two.
+        let mut list3 = Ipv4CidrList::new();
+        list3.insert(Ipv4Cidr::from_str("10.0.0.0/31").unwrap());
+        list3.insert(Ipv4Cidr::from_str("20.0.0.0/31").unwrap());
+        list3.insert(Ipv4Cidr::from_str("30.0.0.0/31").unwrap());
+        let addrs3: Vec<Ipv4Addr> = list3.addresses().collect();
+        assert_eq!(
+            vec![
+                Ipv4Addr::from_str("10.0.0.0").unwrap(),
+                Ipv4Addr::from_str("10.0.0.1").unwrap(),
+                Ipv4Addr::from_str("20.0.0.0").unwrap(),
+                Ipv4Addr::from_str("20.0.0.1").unwrap(),
+                Ipv4Addr::from_str("30.0.0.0").unwrap(),
+                Ipv4Addr::from_str("30.0.0.1").unwrap(),
+            ],
+            addrs3
+        );
+    }
+
+    #[test]
+    fn ipv4_range_tests() {
+        let range = Ipv4Range::from_str("1.0.0.0-1.0.0.255").unwrap();
+        assert_eq!("1.0.0.0-1.0.0.255", range.to_string());
+        assert_eq!(256, range.len());
+        assert!(range.contains_ip(&Ipv4Addr::from_str("1.0.0.128").unwrap()));
+        assert!(!range.contains_ip(&Ipv4Addr::from_str("1.0.1.0").unwrap()));
+
+        let mut expected = Ipv4CidrList::new();
+        expected.insert(Ipv4Cidr::from_str("1.0.0.0/24").unwrap());
+        assert_eq!(expected, range.to_cidr_list());
+
+        assert_eq!(
+            vec![range.clone()],
+            range.to_cidr_list().to_ranges()
+        );
+
+        assert!(Ipv4Range::from_str("1.0.0.255-1.0.0.0").is_err());
+        assert!(Ipv4Range::from_str("not-a-range").is_err());
+    }
+
     #[quickcheck]
     fn convert_tests(xs: u32, ys: u8) -> bool {
         match Ipv4Cidr::new(xs, ys % 33) {
@@ -463,8 +1001,8 @@ mod tests {
     #[quickcheck]
     fn check_cidr_list_change(from: u32, to: u32) -> bool {
         from > to
-            || Ipv4CidrList::from_range(from, to).to_range()
-                == vec![(Ipv4Addr::from(from), Ipv4Addr::from(to))]
+            || Ipv4CidrList::from_range(from, to).to_ranges()
+                == vec![Ipv4Range::new(Ipv4Addr::from(from), Ipv4Addr::from(to)).unwrap()]
     }
 
     #[quickcheck]
@@ -474,7 +1012,7 @@ mod tests {
         let mut list = Ipv4CidrList::from_range(from, to);
         let mut modl = list.clone();
         if modl.remove(&c) {
-            assert_eq!(true, modl.insert(c));
+            assert!(modl.insert(c));
             list.insert(d);
         }
         list == modl
@@ -0,0 +1,478 @@
+//! Ipv6 CIDR functions
+//!
+//! This module provides:
+//!
+//! * [`Ipv6Cidr`] Ipv6 CIDR structure.
+//! * [`Ipv6CidrList`] Ipv6 CIDR collection structure.
+//!
+//!
+
+use std::collections::btree_map::Iter;
+use std::collections::btree_map::IterMut;
+use std::collections::LinkedList;
+use std::fmt::Display;
+use std::fmt::Error;
+use std::fmt::Formatter;
+use std::net::Ipv6Addr;
+use std::str::FromStr;
+
+use std::collections::BTreeMap;
+use std::collections::HashSet;
+
+/// Ipv6 CIDR structure
+#[derive(Eq, PartialEq, Debug, Clone)]
+pub struct Ipv6Cidr {
+    /// First IP
+    net: u128,
+    /// Size of CIDR blocks in 2^size.
+    size: u8,
+}
+
+const NOT_POSSIBLE: &str = "Not possible";
+
+impl Ipv6Cidr {
+    /// Create CIDR from ip and mask.
+    pub fn new(mut net: u128, mask: u8) -> Result<Self, String> {
+        if mask > 128 {
+            return Err("Mask should equal or less then 128.".to_string());
+        }
+        if mask == 0 {
+            net = 0
+        } else if mask < 128 {
+            net = (net >> (128 - mask)) << (128 - mask)
+        }
+        let size = 128 - mask;
+        Ok(Ipv6Cidr { net, size })
+    }
+
+    /// Create CIDR from ip and mask
+    pub fn from_ip(ip: Ipv6Addr, mask: u8) -> Result<Self, String> {
+        Self::new(u128::from(ip), mask)
+    }
+
+    /// Get the first ip in the CIDR blocks.
+    pub fn first_ip(&self) -> Ipv6Addr {
+        Ipv6Addr::from(self.net)
+    }
+
+    /// Get the last ip in the CIDR blocks.
+    pub fn last_ip(&self) -> Ipv6Addr {
+        Ipv6Addr::from(self.to_range().1)
+    }
+
+    /// Get the mask
+    pub fn mask(&self) -> u8 {
+        128 - self.size
+    }
+
+    /// Check if the ip is in this CIDR block.
+    pub fn contains_ip(&self, ip: &Ipv6Addr) -> bool {
+        if self.size == 128 {
+            return true;
+        }
+        self.net >> self.size == u128::from(*ip) >> self.size
+    }
+
+    /// Check if the current CIDR block contains other CIDR block.
+    pub fn contains_cidr(&self, cidr: &Ipv6Cidr) -> bool {
+        if self.size == 128 {
+            return true;
+        }
+        if self.size < cidr.size {
+            return false;
+        }
+        self.net >> self.size == cidr.net >> self.size
+    }
+
+    /// Convert CIDR block to u128 range.
+    pub fn to_range(&self) -> (u128, u128) {
+        if self.size == 128 {
+            return (0, u128::MAX);
+        }
+        (self.net, self.net + (2u128.pow(self.size as u32) - 1))
+    }
+    /// Convert CIDR block to ip range.
+    pub fn to_ip_range(&self) -> (Ipv6Addr, Ipv6Addr) {
+        let (f, t) = self.to_range();
+        (Ipv6Addr::from(f), Ipv6Addr::from(t))
+    }
+}
+
+impl FromStr for Ipv6Cidr {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.splitn(2, '/');
+        let addr = parts.next().ok_or_else(|| "Invalid CIDR format.".to_owned())?;
+        let ip = Ipv6Addr::from_str(addr).map_err(|_| "Invalid CIDR format.".to_owned())?;
+        let mask = match parts.next() {
+            Some(m) => m.parse::<u8>().map_err(|_| "Invalid CIDR format.".to_owned())?,
+            None => 128,
+        };
+        Ipv6Cidr::new(u128::from(ip), mask)
+    }
+}
+
+impl From<Ipv6Addr> for Ipv6Cidr {
+    fn from(addr: Ipv6Addr) -> Self {
+        Ipv6Cidr::from(u128::from(addr))
+    }
+}
+
+impl From<u128> for Ipv6Cidr {
+    fn from(addr: u128) -> Self {
+        Ipv6Cidr::new(addr, 128).expect(NOT_POSSIBLE)
+    }
+}
+
+impl Display for Ipv6Cidr {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        write!(f, "{}/{}", self.first_ip(), self.mask())
+    }
+}
+
+/// Ipv6 CIDR collection structure.
+#[derive(Eq, PartialEq, Clone)]
+pub struct Ipv6CidrList {
+    inner: BTreeMap<u128, Ipv6Cidr>,
+}
+
+impl Display for Ipv6CidrList {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        for (&_, v) in self.inner.iter() {
+            writeln!(f, "{}", v)?;
+        }
+        Ok(())
+    }
+}
+
+impl IntoIterator for Ipv6CidrList {
+    type Item = (u128, Ipv6Cidr);
+    type IntoIter = std::collections::btree_map::IntoIter<u128, Ipv6Cidr>;
+    fn into_iter(self) -> <Self as IntoIterator>::IntoIter {
+        self.inner.into_iter()
+    }
+}
+
+impl Default for Ipv6CidrList {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Ipv6CidrList {
+    /// Create empty collection.
+    pub fn new() -> Self {
+        Ipv6CidrList {
+            inner: BTreeMap::new(),
+        }
+    }
+
+    /// Generate collection from ip range, can result multiple CIDR blocks.
+    pub fn from_range(from: u128, to: u128) -> Self {
+        let mut list = Ipv6CidrList::new();
+        if from > to {
+            return list;
+        }
+        fn build(from: u128, to: u128, list: &mut Ipv6CidrList) {
+            let mut f = from;
+            let mut t = to;
+            let mut m = 0;
+            while f != t {
+                f >>= 1;
+                t >>= 1;
+                m += 1;
+            }
+            let block =
+                Ipv6Cidr::new(if m == 128 { 0 } else { f << m }, 128 - m).expect(NOT_POSSIBLE);
+            if block.to_range() == (from, to) {
+                list.insert(block);
+                return;
+            }
+            let mid = ((f << 1) + 1) << (m - 1);
+            build(from, mid - 1, list);
+            build(mid, to, list);
+        }
+        build(from, to, &mut list);
+        list
+    }
+
+    /// Generate collection from ip range, can result multiple CIDR blocks.
+    pub fn from_ip_range(from: Ipv6Addr, to: Ipv6Addr) -> Self {
+        Self::from_range(u128::from(from), u128::from(to))
+    }
+
+    /// Iterate all CIDR blocks.
+    pub fn iter(&self) -> Iter<'_, u128, Ipv6Cidr> {
+        self.inner.iter()
+    }
+
+    /// Iterate all mutable CIDR blocks.
+    pub fn iter_mut(&mut self) -> IterMut<'_, u128, Ipv6Cidr> {
+        self.inner.iter_mut()
+    }
+
+    /// Export CIDR blocks to ip ranges, normally ip ranges item size is smaller than CIDR blocks.
+    pub fn to_range(&self) -> Vec<(Ipv6Addr, Ipv6Addr)> {
+        let mut v = vec![];
+        let mut iter = self.iter();
+        let mut f;
+        if let Some((_, cidr)) = iter.next() {
+            f = cidr.to_range();
+        } else {
+            return v;
+        }
+        for (_, cidr) in iter {
+            let t = cidr.to_range();
+            if t.0 == f.1 + 1 {
+                f.1 = t.1;
+            } else {
+                v.push((Ipv6Addr::from(f.0), Ipv6Addr::from(f.1)));
+                f = t;
+            }
+        }
+        v.push((Ipv6Addr::from(f.0), Ipv6Addr::from(f.1)));
+        v
+    }
+
+    /// Check if collection contains ip.
+    pub fn contains_ip(&self, ip: &Ipv6Addr) -> bool {
+        self.contains_cidr(&Ipv6Cidr::from(*ip))
+    }
+
+    /// Check if collection contains CIDR block.
+    pub fn contains_cidr(&self, cidr: &Ipv6Cidr) -> bool {
+        self.search_parent(cidr).is_some()
+    }
+
+    /// Get the parent CIDR block with specified CIDR block.
+    pub fn search_parent(&self, cidr: &Ipv6Cidr) -> Option<&Ipv6Cidr> {
+        let mut net = cidr.net;
+        let mut size = cidr.size;
+        loop {
+            if let Some(v) = self.inner.get(&net) {
+                if v.size >= size {
+                    return Some(v);
+                }
+            }
+            if size == 128 {
+                return None;
+            }
+            net >>= size;
+            while net & 1 == 0 {
+                net >>= 1;
+                size += 1;
+                if size == 128 {
+                    return None;
+                }
+            }
+            net = (net - 1) << size;
+            size += 1;
+        }
+    }
+
+    fn delete_in_range(&mut self, cidr: &Ipv6Cidr) -> bool {
+        let (f, t) = cidr.to_range();
+        let mut rem = LinkedList::new();
+        for (&k, v) in self.inner.range(f..=t) {
+            if cidr.contains_cidr(v) {
+                rem.push_back(k);
+            }
+        }
+        let changed = !rem.is_empty();
+        for k in rem {
+            self.inner.remove(&k);
+        }
+        changed
+    }
+
+    /// Insert a CIDR block into the collection. Return `true` means collection is modified.
+    pub fn insert(&mut self, mut cidr: Ipv6Cidr) -> bool {
+        self.delete_in_range(&cidr);
+        if self.contains_cidr(&cidr) {
+            return false;
+        }
+        loop {
+            //Merge
+            if cidr.size < 128 {
+                let block = cidr.net >> cidr.size;
+                let pair = if block & 1 == 0 {
+                    (block + 1) << cidr.size
+                } else {
+                    (block - 1) << cidr.size
+                };
+                if let Some(v) = self.inner.get(&pair) {
+                    if v.size == cidr.size {
+                        self.inner.remove(&pair);
+                        cidr = Ipv6Cidr::new(pair, 127 - cidr.size).expect(NOT_POSSIBLE);
+                        continue;
+                    }
+                }
+            }
+            self.inner.insert(cidr.net, cidr);
+            return true;
+        }
+    }
+
+    /// Remove CIDR blocks.
+    pub fn remove(&mut self, cidr: &Ipv6Cidr) -> bool {
+        if self.delete_in_range(cidr) {
+            return true;
+        }
+        let mut add = HashSet::new();
+        if let Some(v) = self.search_parent(cidr) {
+            if v == cidr {
+                return false;
+            }
+            let v = v.clone();
+            self.inner.remove(&v.net);
+            let (a2, a3) = cidr.to_range();
+            let (a1, a4) = v.to_range();
+            if a1 < a2 {
+                add.insert((a1, a2 - 1));
+            }
+            if a3 < a4 {
+                add.insert((a3 + 1, a4));
+            }
+        }
+        for (a, b) in add {
+            for (_, v) in Self::from_range(a, b) {
+                self.insert(v);
+            }
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quickcheck::quickcheck;
+    #[test]
+    fn block_parse_tests() {
+        assert_eq!("::/0", Ipv6Cidr::new(0, 0).unwrap().to_string());
+        assert_eq!(
+            "ffff:ffff:ffff:ffff:ffff:ffff:ffff:ffff/128",
+            Ipv6Cidr::new(u128::MAX, 128).unwrap().to_string()
+        );
+        assert_eq!(
+            "::1/128",
+            Ipv6Cidr::new(1, 128).unwrap().to_string()
+        );
+    }
+    #[test]
+    fn block_list_tests() {
+        let mut list = Ipv6CidrList::new();
+        list.insert(Ipv6Cidr::from_str("::/1").unwrap());
+        list.insert(Ipv6Cidr::from_str("8000::/1").unwrap());
+        assert_eq!(1, list.inner.len());
+    }
+    #[test]
+    fn range_parse_tests() {
+        let from = Ipv6Addr::from_str("::").unwrap();
+        let to = Ipv6Addr::from_str("ffff:ffff:ffff:ffff:ffff:ffff:ffff:ffff").unwrap();
+        let list = Ipv6CidrList::from_ip_range(from, to);
+        assert_eq!("::/0", list.to_string().trim());
+    }
+
+    #[test]
+    fn remove_cidr_tests() {
+        let from = Ipv6Addr::from_str("::").unwrap();
+        let to = Ipv6Addr::from_str("ffff:ffff:ffff:ffff:ffff:ffff:ffff:ffff").unwrap();
+        let rem = Ipv6Cidr::from_str("::/8").unwrap();
+        let mut list = Ipv6CidrList::from_ip_range(from, to);
+        list.remove(&rem);
+    }
+
+    #[test]
+    fn to_range_multi_block_tests() {
+        // Three disjoint blocks: a buggy `to_range()` that never advances past the first run
+        // would repeat the first block's range instead of reporting the other two.
+        let mut list = Ipv6CidrList::new();
+        list.insert(Ipv6Cidr::from_str("::/127").unwrap());
+        list.insert(Ipv6Cidr::from_str("2000::/127").unwrap());
+        list.insert(Ipv6Cidr::from_str("3000::/127").unwrap());
+        assert_eq!(
+            vec![
+                (
+                    Ipv6Addr::from_str("::").unwrap(),
+                    Ipv6Addr::from_str("::1").unwrap()
+                ),
+                (
+                    Ipv6Addr::from_str("2000::").unwrap(),
+                    Ipv6Addr::from_str("2000::1").unwrap()
+                ),
+                (
+                    Ipv6Addr::from_str("3000::").unwrap(),
+                    Ipv6Addr::from_str("3000::1").unwrap()
+                ),
+            ],
+            list.to_range()
+        );
+    }
+
+    #[quickcheck]
+    fn convert_tests(xs: u128, ys: u8) -> bool {
+        match Ipv6Cidr::new(xs, ys % 129) {
+            Ok(ip) => ip == Ipv6Cidr::from_str(&ip.to_string()).unwrap(),
+            _ => false,
+        }
+    }
+
+    #[quickcheck]
+    fn check_contains_ip(ip: u128, i: u8) -> bool {
+        Ipv6Cidr::new(ip, i % 129)
+            .unwrap()
+            .contains_ip(&Ipv6Addr::from(ip))
+    }
+
+    #[quickcheck]
+    fn check_contains_cidr(ip: u128, i: u8) -> bool {
+        let i = i % 128;
+        let a0 = Ipv6Cidr::new(ip, i).unwrap();
+        let a1 = Ipv6Cidr::new(ip, i + 1).unwrap();
+        a0.contains_cidr(&a1) && !a1.contains_cidr(&a0)
+    }
+
+    #[quickcheck]
+    fn check_to_range(ip: u128, i: u8) -> bool {
+        let cidr = Ipv6Cidr::new(ip, i % 128).unwrap();
+        let (from, to) = cidr.to_range();
+        if from > to {
+            return false;
+        }
+        if cidr.size == 128 {
+            return from == 0 && to == u128::MAX;
+        }
+        let count = to - from + 1;
+        count >> cidr.size == 1 && count.count_ones() == 1
+    }
+
+    #[quickcheck]
+    fn check_cidr_list(ip: u128) -> bool {
+        let mut list = Ipv6CidrList::new();
+        for j in 1..=128 {
+            list.insert(Ipv6Cidr::new(ip, j).unwrap());
+        }
+        list.inner.len() == 1
+    }
+
+    #[quickcheck]
+    fn check_cidr_list_change(from: u128, to: u128) -> bool {
+        from > to
+            || Ipv6CidrList::from_range(from, to).to_range()
+                == vec![(Ipv6Addr::from(from), Ipv6Addr::from(to))]
+    }
+
+    #[quickcheck]
+    fn check_cidr_list_remove(from: u128, to: u128, rem: u128, m: u8) -> bool {
+        let c = Ipv6Cidr::new(rem, m % 129).unwrap();
+        let d = c.clone();
+        let mut list = Ipv6CidrList::from_range(from, to);
+        let mut modl = list.clone();
+        if modl.remove(&c) {
+            assert!(modl.insert(c));
+            list.insert(d);
+        }
+        list == modl
+    }
+}
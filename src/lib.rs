@@ -5,6 +5,9 @@
 //! `Ipv4CidrList` is a collection of CIDRs, it keeps CIDR in order, and can merge newly inserted CIDRs.
 //!
 //! ```
+//!   use cidr::ipv4::{Ipv4Cidr, Ipv4CidrList};
+//!   use std::str::FromStr;
+//!
 //!   let mut list = Ipv4CidrList::new();
 //!   list.insert(Ipv4Cidr::from_str("0.0.0.0/1").unwrap());
 //!   list.insert(Ipv4Cidr::from_str("128.0.0.0/2").unwrap());
@@ -15,6 +18,10 @@
 //! Parse from ip range:
 //!
 //! ```
+//!   use cidr::ipv4::Ipv4CidrList;
+//!   use std::net::Ipv4Addr;
+//!   use std::str::FromStr;
+//!
 //!   let from = Ipv4Addr::from_str("1.0.0.0").unwrap();
 //!   let to = Ipv4Addr::from_str("1.0.0.255").unwrap();
 //!   let list = Ipv4CidrList::from_range(u32::from(from), u32::from(to));
@@ -26,3 +33,203 @@
 extern crate quickcheck_macros;
 
 pub mod ipv4;
+pub mod ipv6;
+pub mod trie;
+
+use ipv4::{Ipv4Cidr, Ipv4CidrList};
+use ipv6::{Ipv6Cidr, Ipv6CidrList};
+use std::fmt::Display;
+use std::fmt::Error;
+use std::fmt::Formatter;
+use std::net::IpAddr;
+use std::str::FromStr;
+
+/// A CIDR block that may be either IPv4 or IPv6.
+#[derive(Eq, PartialEq, Debug, Clone)]
+pub enum AnyCidr {
+    /// An IPv4 CIDR block.
+    V4(Ipv4Cidr),
+    /// An IPv6 CIDR block.
+    V6(Ipv6Cidr),
+}
+
+impl AnyCidr {
+    /// Check if the ip is in this CIDR block.
+    pub fn contains_ip(&self, ip: &IpAddr) -> bool {
+        match (self, ip) {
+            (AnyCidr::V4(cidr), IpAddr::V4(ip)) => cidr.contains_ip(ip),
+            (AnyCidr::V6(cidr), IpAddr::V6(ip)) => cidr.contains_ip(ip),
+            _ => false,
+        }
+    }
+
+    /// Check if the current CIDR block contains other CIDR block.
+    ///
+    /// Returns `false` if the two blocks' address families differ.
+    pub fn contains_cidr(&self, cidr: &AnyCidr) -> bool {
+        match (self, cidr) {
+            (AnyCidr::V4(a), AnyCidr::V4(b)) => a.contains_cidr(b),
+            (AnyCidr::V6(a), AnyCidr::V6(b)) => a.contains_cidr(b),
+            _ => false,
+        }
+    }
+
+    /// Get the mask.
+    pub fn mask(&self) -> u8 {
+        match self {
+            AnyCidr::V4(cidr) => cidr.mask(),
+            AnyCidr::V6(cidr) => cidr.mask(),
+        }
+    }
+}
+
+impl FromStr for AnyCidr {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.contains(':') {
+            Ipv6Cidr::from_str(s).map(AnyCidr::V6)
+        } else {
+            Ipv4Cidr::from_str(s).map(AnyCidr::V4)
+        }
+    }
+}
+
+impl Display for AnyCidr {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        match self {
+            AnyCidr::V4(cidr) => cidr.fmt(f),
+            AnyCidr::V6(cidr) => cidr.fmt(f),
+        }
+    }
+}
+
+/// A collection of CIDR blocks that may be either IPv4 or IPv6.
+#[derive(Eq, PartialEq, Clone)]
+pub enum AnyCidrList {
+    /// A collection of IPv4 CIDR blocks.
+    V4(Ipv4CidrList),
+    /// A collection of IPv6 CIDR blocks.
+    V6(Ipv6CidrList),
+}
+
+impl AnyCidrList {
+    /// Insert a CIDR block into the collection. Return `true` means collection is modified.
+    ///
+    /// Returns `false` if the CIDR block's address family does not match the collection's.
+    pub fn insert(&mut self, cidr: AnyCidr) -> bool {
+        match (self, cidr) {
+            (AnyCidrList::V4(list), AnyCidr::V4(cidr)) => list.insert(cidr),
+            (AnyCidrList::V6(list), AnyCidr::V6(cidr)) => list.insert(cidr),
+            _ => false,
+        }
+    }
+
+    /// Check if collection contains ip.
+    pub fn contains_ip(&self, ip: &IpAddr) -> bool {
+        match (self, ip) {
+            (AnyCidrList::V4(list), IpAddr::V4(ip)) => list.contains_ip(ip),
+            (AnyCidrList::V6(list), IpAddr::V6(ip)) => list.contains_ip(ip),
+            _ => false,
+        }
+    }
+
+    /// Check if collection contains CIDR block.
+    ///
+    /// Returns `false` if the CIDR block's address family does not match the collection's.
+    pub fn contains_cidr(&self, cidr: &AnyCidr) -> bool {
+        match (self, cidr) {
+            (AnyCidrList::V4(list), AnyCidr::V4(cidr)) => list.contains_cidr(cidr),
+            (AnyCidrList::V6(list), AnyCidr::V6(cidr)) => list.contains_cidr(cidr),
+            _ => false,
+        }
+    }
+
+    /// Remove a CIDR block from the collection. Return `true` means collection is modified.
+    ///
+    /// Returns `false` if the CIDR block's address family does not match the collection's.
+    pub fn remove(&mut self, cidr: &AnyCidr) -> bool {
+        match (self, cidr) {
+            (AnyCidrList::V4(list), AnyCidr::V4(cidr)) => list.remove(cidr),
+            (AnyCidrList::V6(list), AnyCidr::V6(cidr)) => list.remove(cidr),
+            _ => false,
+        }
+    }
+}
+
+impl Display for AnyCidrList {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        match self {
+            AnyCidrList::V4(list) => list.fmt(f),
+            AnyCidrList::V6(list) => list.fmt(f),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn any_cidr_from_str_family_tests() {
+        assert_eq!(
+            AnyCidr::V4(Ipv4Cidr::from_str("10.0.0.0/8").unwrap()),
+            AnyCidr::from_str("10.0.0.0/8").unwrap()
+        );
+        assert_eq!(
+            AnyCidr::V6(Ipv6Cidr::from_str("::/8").unwrap()),
+            AnyCidr::from_str("::/8").unwrap()
+        );
+        assert!(AnyCidr::from_str("not-a-cidr").is_err());
+    }
+
+    #[test]
+    fn any_cidr_cross_family_contains_ip_tests() {
+        let v4 = AnyCidr::V4(Ipv4Cidr::from_str("10.0.0.0/8").unwrap());
+        let v6 = AnyCidr::V6(Ipv6Cidr::from_str("::/8").unwrap());
+
+        assert!(v4.contains_ip(&IpAddr::from_str("10.1.2.3").unwrap()));
+        assert!(!v4.contains_ip(&IpAddr::from_str("::1").unwrap()));
+        assert!(v6.contains_ip(&IpAddr::from_str("::1").unwrap()));
+        assert!(!v6.contains_ip(&IpAddr::from_str("10.1.2.3").unwrap()));
+    }
+
+    #[test]
+    fn any_cidr_list_cross_family_tests() {
+        let mut v4_list = AnyCidrList::V4(Ipv4CidrList::new());
+        assert!(v4_list.insert(AnyCidr::V4(Ipv4Cidr::from_str("10.0.0.0/8").unwrap())));
+        assert!(!v4_list.insert(AnyCidr::V6(Ipv6Cidr::from_str("::/8").unwrap())));
+
+        assert!(v4_list.contains_ip(&IpAddr::from_str("10.1.2.3").unwrap()));
+        assert!(!v4_list.contains_ip(&IpAddr::from_str("::1").unwrap()));
+    }
+
+    #[test]
+    fn any_cidr_contains_cidr_tests() {
+        let v4 = AnyCidr::V4(Ipv4Cidr::from_str("10.0.0.0/8").unwrap());
+        let v6 = AnyCidr::V6(Ipv6Cidr::from_str("::/8").unwrap());
+
+        assert!(v4.contains_cidr(&AnyCidr::from_str("10.1.0.0/16").unwrap()));
+        assert!(!v4.contains_cidr(&v6));
+        assert!(v6.contains_cidr(&AnyCidr::from_str("::1/128").unwrap()));
+        assert!(!v6.contains_cidr(&v4));
+    }
+
+    #[test]
+    fn any_cidr_mask_tests() {
+        assert_eq!(8, AnyCidr::from_str("10.0.0.0/8").unwrap().mask());
+        assert_eq!(8, AnyCidr::from_str("::/8").unwrap().mask());
+    }
+
+    #[test]
+    fn any_cidr_list_contains_cidr_and_remove_tests() {
+        let mut v4_list = AnyCidrList::V4(Ipv4CidrList::new());
+        v4_list.insert(AnyCidr::V4(Ipv4Cidr::from_str("10.0.0.0/8").unwrap()));
+
+        assert!(v4_list.contains_cidr(&AnyCidr::from_str("10.1.0.0/16").unwrap()));
+        assert!(!v4_list.contains_cidr(&AnyCidr::from_str("::/8").unwrap()));
+
+        assert!(!v4_list.remove(&AnyCidr::from_str("::/8").unwrap()));
+        assert!(v4_list.remove(&AnyCidr::from_str("10.0.0.0/8").unwrap()));
+        assert!(!v4_list.contains_ip(&IpAddr::from_str("10.1.2.3").unwrap()));
+    }
+}
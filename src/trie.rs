@@ -0,0 +1,100 @@
+//! Internal binary radix trie for longest-prefix-match lookups.
+//!
+//! Keyed on the high bits of an address up to 128 bits wide, this gives deterministic
+//! O(width) insert/remove/lookup independent of the number of entries stored, instead of
+//! climbing the prefix space one merged block at a time. It backs [`crate::ipv4::Ipv4CidrList`]
+//! today and is generic enough to back an IPv6 variant at depth 128 later.
+
+struct Node<V> {
+    value: Option<V>,
+    children: [Option<Box<Node<V>>>; 2],
+}
+
+impl<V> Node<V> {
+    fn empty() -> Self {
+        Node {
+            value: None,
+            children: [None, None],
+        }
+    }
+}
+
+impl<V: Clone> Clone for Node<V> {
+    fn clone(&self) -> Self {
+        Node {
+            value: self.value.clone(),
+            children: [self.children[0].clone(), self.children[1].clone()],
+        }
+    }
+}
+
+/// A binary radix trie mapping address prefixes to values.
+pub struct Trie<V> {
+    width: u8,
+    root: Node<V>,
+}
+
+impl<V: Clone> Clone for Trie<V> {
+    fn clone(&self) -> Self {
+        Trie {
+            width: self.width,
+            root: self.root.clone(),
+        }
+    }
+}
+
+impl<V> Trie<V> {
+    /// Create an empty trie over addresses of `width` bits (32 for IPv4, 128 for IPv6).
+    pub fn new(width: u8) -> Self {
+        Trie {
+            width,
+            root: Node::empty(),
+        }
+    }
+
+    fn bit(width: u8, addr: u128, i: u8) -> usize {
+        ((addr >> (width - 1 - i)) & 1) as usize
+    }
+
+    /// Insert `value` at the prefix formed by the top `prefix_len` bits of `addr`.
+    pub fn insert(&mut self, addr: u128, prefix_len: u8, value: V) {
+        let width = self.width;
+        let mut node = &mut self.root;
+        for i in 0..prefix_len {
+            let bit = Self::bit(width, addr, i);
+            node = node.children[bit].get_or_insert_with(|| Box::new(Node::empty()));
+        }
+        node.value = Some(value);
+    }
+
+    /// Remove the value stored exactly at the prefix formed by the top `prefix_len` bits of
+    /// `addr`, if any.
+    pub fn remove(&mut self, addr: u128, prefix_len: u8) -> Option<V> {
+        let width = self.width;
+        let mut node = &mut self.root;
+        for i in 0..prefix_len {
+            let bit = Self::bit(width, addr, i);
+            node = node.children[bit].as_mut()?;
+        }
+        node.value.take()
+    }
+
+    /// Find the value stored at the longest matching prefix of `addr`, considering only
+    /// prefixes no longer than `max_len` bits.
+    pub fn longest_match(&self, addr: u128, max_len: u8) -> Option<&V> {
+        let mut node = &self.root;
+        let mut found = node.value.as_ref();
+        for i in 0..max_len {
+            match &node.children[Self::bit(self.width, addr, i)] {
+                Some(next) => {
+                    node = next;
+                    if node.value.is_some() {
+                        found = node.value.as_ref();
+                    }
+                }
+                None => break,
+            }
+        }
+        found
+    }
+}